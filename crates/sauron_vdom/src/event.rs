@@ -0,0 +1,482 @@
+//! Generic, backend-agnostic event types.
+//!
+//! These are what callbacks registered with `on*` functions actually receive:
+//! the browser event is mapped into one of these plain structs, either by the
+//! `mapper` module in `sauron::html::events` or, for types that implement
+//! [`StaticEvent`], by the type itself.
+//!
+//! Of the built-in event types here, only [`InputEvent`] implements
+//! `StaticEvent` — `MouseEvent` and `KeyEvent` each back several distinct DOM
+//! event names, which `StaticEvent::EVENT_TYPE` can't represent, so they stay
+//! wired up through `mapper` instead.
+
+use log::error;
+use wasm_bindgen::JsCast;
+
+/// Implemented by types that can be extracted from a single, statically-known
+/// DOM event type, e.g. `InputEvent` from `"input"`. This is what lets
+/// `sauron::html::events::on_event` register a callback for a typed event
+/// without `declare_events!` needing to know about it — third-party crates
+/// can implement this for their own events (a `CustomEvent` with a typed
+/// `detail`, media events, etc.) and call `on_event::<TheirEvent, _>(cb)`.
+pub trait StaticEvent: Sized {
+    /// The DOM event type this is extracted from, e.g. `"click"`.
+    const EVENT_TYPE: &'static str;
+
+    /// Build `Self` from the raw DOM event. Callers are expected to only
+    /// invoke this for events of `EVENT_TYPE`, hence "unchecked".
+    fn unchecked_from_event(event: web_sys::Event) -> Self;
+}
+
+/// The mouse button that triggered a `MouseEvent`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum MouseButton {
+    /// The primary button, usually the left button.
+    Left,
+    /// The auxiliary button, usually the wheel/middle button.
+    Middle,
+    /// The secondary button, usually the right button.
+    Right,
+    /// The fourth button, commonly mapped to "browser back".
+    WheelUp,
+    /// The fifth button, commonly mapped to "browser forward".
+    WheelDown,
+}
+
+impl Default for MouseButton {
+    fn default() -> Self {
+        MouseButton::Left
+    }
+}
+
+impl MouseButton {
+    /// The bit this button occupies in the `MouseEvent.buttons` mask.
+    fn bit(self) -> u16 {
+        match self {
+            MouseButton::Left => 0b0_0001,
+            MouseButton::Right => 0b0_0010,
+            MouseButton::Middle => 0b0_0100,
+            MouseButton::WheelUp => 0b0_1000,
+            MouseButton::WheelDown => 0b1_0000,
+        }
+    }
+}
+
+const ALL_MOUSE_BUTTONS: [MouseButton; 5] = [
+    MouseButton::Left,
+    MouseButton::Right,
+    MouseButton::Middle,
+    MouseButton::WheelUp,
+    MouseButton::WheelDown,
+];
+
+/// The set of mouse buttons currently held down, decoded from the bitmask
+/// `MouseEvent.buttons` reports (as opposed to `MouseEvent.button`, which
+/// only says which single button triggered the event).
+#[derive(Debug, PartialEq, Eq, Default, Clone, Copy)]
+pub struct MouseButtonSet(u16);
+
+impl MouseButtonSet {
+    /// Build a set from the raw `buttons()` bitmask.
+    pub fn from_bits(bits: u16) -> Self {
+        MouseButtonSet(bits)
+    }
+
+    /// Whether `button` is currently held down.
+    pub fn contains(&self, button: MouseButton) -> bool {
+        self.0 & button.bit() != 0
+    }
+
+    /// Whether no buttons are currently held down.
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// How many buttons are currently held down.
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// Iterate over the buttons currently held down.
+    pub fn iter(&self) -> impl Iterator<Item = MouseButton> + '_ {
+        let set = *self;
+        ALL_MOUSE_BUTTONS.iter().copied().filter(move |b| set.contains(*b))
+    }
+}
+
+impl IntoIterator for MouseButtonSet {
+    type Item = MouseButton;
+    type IntoIter = std::vec::IntoIter<MouseButton>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter().collect::<Vec<_>>().into_iter()
+    }
+}
+
+/// The position of a pointer, in the various coordinate spaces the DOM
+/// exposes on `MouseEvent`/`TouchEvent`/`PointerEvent`.
+#[derive(Debug, PartialEq, Default, Clone, Copy)]
+pub struct Coordinate {
+    /// x coordinate relative to the viewport.
+    pub client_x: i32,
+    /// y coordinate relative to the viewport.
+    pub client_y: i32,
+    /// x movement relative to the previous `mousemove` event.
+    pub movement_x: i32,
+    /// y movement relative to the previous `mousemove` event.
+    pub movement_y: i32,
+    /// x coordinate relative to the edge of the target node's padding box.
+    pub offset_x: i32,
+    /// y coordinate relative to the edge of the target node's padding box.
+    pub offset_y: i32,
+    /// x coordinate relative to the screen.
+    pub screen_x: i32,
+    /// y coordinate relative to the screen.
+    pub screen_y: i32,
+    /// x coordinate relative to the whole document.
+    pub x: i32,
+    /// y coordinate relative to the whole document.
+    pub y: i32,
+}
+
+/// The modifier keys held down when an event fired.
+#[derive(Debug, PartialEq, Default, Clone, Copy)]
+pub struct Modifier {
+    /// Whether `Alt` was held down.
+    pub alt_key: bool,
+    /// Whether `Ctrl` was held down.
+    pub ctrl_key: bool,
+    /// Whether `Meta` (Cmd/Windows key) was held down.
+    pub meta_key: bool,
+    /// Whether `Shift` was held down.
+    pub shift_key: bool,
+}
+
+/// A mouse event, e.g. `click`, `mousedown`, `mousemove`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MouseEvent {
+    /// The DOM event type that was fired, e.g. `"click"`.
+    pub r#type: &'static str,
+    /// Where the event happened.
+    pub coordinate: Coordinate,
+    /// The modifier keys held down when the event fired.
+    pub modifier: Modifier,
+    /// The single button that triggered this event.
+    pub button: MouseButton,
+    /// Every button held down at the time this event fired.
+    pub buttons: MouseButtonSet,
+}
+
+impl MouseEvent {
+    /// The set of buttons currently held down. Useful for drag/drawing code
+    /// that needs to know which buttons are down, not just which one
+    /// triggered this particular event.
+    pub fn held_buttons(&self) -> MouseButtonSet {
+        self.buttons
+    }
+
+    /// Build a `MouseEvent` from the raw DOM event. `MouseEvent` backs many
+    /// distinct DOM event types (`click`, `mouseup`, `mousemove`, ...), so
+    /// unlike `InputEvent` it can't implement [`StaticEvent`] — there's no
+    /// single `EVENT_TYPE` to register `on_event::<MouseEvent, _>` under.
+    /// Use the `on*` functions `declare_events!` generates instead (e.g.
+    /// `onclick`, `onmouseup`), which already call this.
+    pub fn unchecked_from_event(event: web_sys::Event) -> Self {
+        let mouse: &web_sys::MouseEvent =
+            event.dyn_ref().expect("Unable to cast to mouse event");
+
+        let coordinate = Coordinate {
+            client_x: mouse.client_x(),
+            client_y: mouse.client_y(),
+            movement_x: mouse.movement_x(),
+            movement_y: mouse.movement_y(),
+            offset_x: mouse.offset_x(),
+            offset_y: mouse.offset_y(),
+            screen_x: mouse.screen_x(),
+            screen_y: mouse.screen_y(),
+            x: mouse.x(),
+            y: mouse.y(),
+        };
+        let modifier = Modifier {
+            alt_key: mouse.alt_key(),
+            ctrl_key: mouse.ctrl_key(),
+            meta_key: mouse.meta_key(),
+            shift_key: mouse.shift_key(),
+        };
+        let button = match mouse.button() {
+            0 => MouseButton::Left,
+            1 => MouseButton::Middle,
+            2 => MouseButton::Right,
+            3 => MouseButton::WheelUp,
+            4 => MouseButton::WheelDown,
+            _ => Default::default(), // defaults to left
+        };
+        let buttons = MouseButtonSet::from_bits(mouse.buttons());
+        let r#type = match &*event.type_() {
+            "click" => "click",
+            "auxclick" => "auxclick",
+            "contextmenu" => "contextmenu",
+            "mouseup" => "mouseup",
+            "mousedown" => "mousedown",
+            "mousemove" => "mousemove",
+            "mouseenter" => "mouseenter",
+            "mouseleave" => "mouseleave",
+            "mouseover" => "mouseover",
+            "mouseout" => "mouseout",
+            "dblclick" => "dblclick",
+            "pointerlockchange" => "pointerlockchange",
+            "pointerlockerror" => "pointerlockerror",
+            "select" => "select",
+            _e => {
+                error!("unhandled event type: {}", _e);
+                panic!("unhandled event type: {}", _e);
+            }
+        };
+
+        MouseEvent {
+            r#type,
+            coordinate,
+            modifier,
+            button,
+            buttons,
+        }
+    }
+}
+
+/// A keyboard event, e.g. `keydown`, `keyup`, `keypress`.
+#[derive(Debug, PartialEq, Default, Clone)]
+pub struct KeyEvent {
+    /// The value of the key pressed, e.g. `"a"`, `"Enter"`, `"Shift"`.
+    pub key: String,
+    /// The modifier keys held down when the event fired.
+    pub modifier: Modifier,
+    /// Whether this key event is a repeat caused by the key being held down.
+    pub repeat: bool,
+    /// Which part of the keyboard the key came from, per `KeyboardEvent.location`.
+    pub location: u32,
+}
+
+impl KeyEvent {
+    /// Build a `KeyEvent` from the raw DOM event. `KeyEvent` backs
+    /// `keydown`/`keyup`/`keypress` alike, so unlike `InputEvent` it can't
+    /// implement [`StaticEvent`] — there's no single `EVENT_TYPE` to
+    /// register `on_event::<KeyEvent, _>` under. Use the `on*` functions
+    /// `declare_events!` generates instead (e.g. `onkeydown`, `onkeyup`),
+    /// which already call this.
+    pub fn unchecked_from_event(event: web_sys::Event) -> Self {
+        if let Some(key_event) = event.dyn_ref::<web_sys::KeyboardEvent>() {
+            let modifier = Modifier {
+                alt_key: key_event.alt_key(),
+                ctrl_key: key_event.ctrl_key(),
+                meta_key: key_event.meta_key(),
+                shift_key: key_event.shift_key(),
+            };
+            KeyEvent {
+                key: key_event.key(),
+                modifier,
+                repeat: key_event.repeat(),
+                location: key_event.location(),
+            }
+        } else {
+            //FIXME: not a keyboard event just make something up,
+            //maybe make the return type optional?
+            KeyEvent::default()
+        }
+    }
+}
+
+/// An `input`/`change` event on a form element.
+#[derive(Debug, PartialEq, Default, Clone)]
+pub struct InputEvent {
+    /// The current value of the input/textarea element.
+    pub value: String,
+}
+
+impl StaticEvent for InputEvent {
+    const EVENT_TYPE: &'static str = "input";
+
+    fn unchecked_from_event(event: web_sys::Event) -> Self {
+        let target: web_sys::EventTarget =
+            event.target().expect("Unable to get event target");
+        let input_event = if let Some(input) =
+            target.dyn_ref::<web_sys::HtmlInputElement>()
+        {
+            Some(InputEvent {
+                value: input.value(),
+            })
+        } else if let Some(textarea) =
+            target.dyn_ref::<web_sys::HtmlTextAreaElement>()
+        {
+            Some(InputEvent {
+                value: textarea.value(),
+            })
+        } else {
+            None
+        };
+
+        input_event.expect(
+            "Expecting an input event from input element or textarea element",
+        )
+    }
+}
+
+/// A single contact point of a `TouchEvent`.
+#[derive(Debug, PartialEq, Default, Clone, Copy)]
+pub struct Touch {
+    /// A unique identifier for this contact point, stable across the touch's lifetime.
+    pub identifier: i32,
+    /// x coordinate relative to the viewport.
+    pub client_x: i32,
+    /// y coordinate relative to the viewport.
+    pub client_y: i32,
+    /// x coordinate relative to the screen.
+    pub screen_x: i32,
+    /// y coordinate relative to the screen.
+    pub screen_y: i32,
+    /// x coordinate relative to the whole document.
+    pub page_x: i32,
+    /// y coordinate relative to the whole document.
+    pub page_y: i32,
+    /// x radius of the ellipse that approximates the contact area, in pixels.
+    pub radius_x: f64,
+    /// y radius of the ellipse that approximates the contact area, in pixels.
+    pub radius_y: f64,
+    /// The amount of pressure applied, between 0.0 and 1.0.
+    pub force: f64,
+}
+
+/// A touch event, e.g. `touchstart`, `touchmove`, `touchend`, `touchcancel`.
+#[derive(Debug, PartialEq, Default, Clone)]
+pub struct TouchEvent {
+    /// Every touch point currently on the surface, regardless of target.
+    pub touches: Vec<Touch>,
+    /// The touch points currently on the surface whose target is this element.
+    pub target_touches: Vec<Touch>,
+    /// The touch points that changed since the last touch event.
+    pub changed_touches: Vec<Touch>,
+    /// The modifier keys held down when the event fired.
+    pub modifier: Modifier,
+}
+
+/// The unit that a `WheelEvent`'s delta values are expressed in, mapped from
+/// `WheelEvent.deltaMode`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DeltaMode {
+    /// The delta values are in pixels (`deltaMode == 0`).
+    Pixel,
+    /// The delta values are in lines (`deltaMode == 1`).
+    Line,
+    /// The delta values are in pages (`deltaMode == 2`).
+    Page,
+}
+
+impl Default for DeltaMode {
+    fn default() -> Self {
+        DeltaMode::Pixel
+    }
+}
+
+impl From<u32> for DeltaMode {
+    fn from(mode: u32) -> Self {
+        match mode {
+            0 => DeltaMode::Pixel,
+            1 => DeltaMode::Line,
+            2 => DeltaMode::Page,
+            _ => DeltaMode::Pixel,
+        }
+    }
+}
+
+/// A thin wrapper around `web_sys::DataTransfer`, the payload carried by
+/// drag-and-drop events. Reading/writing it talks to the live browser drag
+/// operation, so unlike the other event types it isn't a plain value.
+#[derive(Debug, Clone)]
+pub struct DataTransfer(web_sys::DataTransfer);
+
+impl DataTransfer {
+    /// Wrap a `web_sys::DataTransfer`.
+    pub fn new(data_transfer: web_sys::DataTransfer) -> Self {
+        DataTransfer(data_transfer)
+    }
+
+    /// The operation that will happen when the drop completes, e.g.
+    /// `"copy"`, `"move"`, `"link"`, or `"none"`.
+    pub fn drop_effect(&self) -> String {
+        self.0.drop_effect()
+    }
+
+    /// Set the operation that should happen when the drop completes.
+    pub fn set_drop_effect(&self, effect: &str) {
+        self.0.set_drop_effect(effect);
+    }
+
+    /// The drag operations allowed for this drag, e.g. `"copy"`, `"move"`,
+    /// `"copyMove"`, or `"all"`.
+    pub fn effect_allowed(&self) -> String {
+        self.0.effect_allowed()
+    }
+
+    /// Set the drag operations allowed for this drag.
+    pub fn set_effect_allowed(&self, effect: &str) {
+        self.0.set_effect_allowed(effect);
+    }
+
+    /// The MIME/format strings of the data being dragged.
+    pub fn types(&self) -> Vec<String> {
+        self.0
+            .types()
+            .iter()
+            .filter_map(|format| format.as_string())
+            .collect()
+    }
+
+    /// Read the dragged data in the given format, e.g. `"text/plain"`.
+    pub fn get_data(&self, format: &str) -> Result<String, wasm_bindgen::JsValue> {
+        self.0.get_data(format)
+    }
+
+    /// Set the dragged data for the given format.
+    pub fn set_data(
+        &self,
+        format: &str,
+        data: &str,
+    ) -> Result<(), wasm_bindgen::JsValue> {
+        self.0.set_data(format, data)
+    }
+
+    /// The files being dragged, if any.
+    pub fn files(&self) -> Vec<web_sys::File> {
+        self.0
+            .files()
+            .map(|list| (0..list.length()).filter_map(|i| list.item(i)).collect())
+            .unwrap_or_default()
+    }
+}
+
+/// A drag-and-drop event, e.g. `dragstart`, `dragover`, `drop`.
+#[derive(Debug, Clone)]
+pub struct DragEvent {
+    /// Where the event happened.
+    pub coordinate: Coordinate,
+    /// The modifier keys held down when the event fired.
+    pub modifier: Modifier,
+    /// The data being dragged, if the browser reported any.
+    pub data_transfer: Option<DataTransfer>,
+}
+
+/// A `wheel` event, carrying the scroll delta a `MouseEvent` doesn't expose.
+#[derive(Debug, PartialEq, Default, Clone)]
+pub struct WheelEvent {
+    /// Where the event happened.
+    pub coordinate: Coordinate,
+    /// The modifier keys held down when the event fired.
+    pub modifier: Modifier,
+    /// The horizontal scroll amount, in units of `delta_mode`.
+    pub delta_x: f64,
+    /// The vertical scroll amount, in units of `delta_mode`.
+    pub delta_y: f64,
+    /// The z-axis scroll amount, in units of `delta_mode`.
+    pub delta_z: f64,
+    /// The unit `delta_x`/`delta_y`/`delta_z` are expressed in.
+    pub delta_mode: DeltaMode,
+}
@@ -2,6 +2,8 @@
 //! The Percy Book.
 
 use crate::{Attribute, Node, Text};
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
 
 /// A Patch encodes an operation that modifies a real DOM element.
 ///
@@ -62,6 +64,25 @@ where
     RemoveEventListener(&'a T, NodeIdx, Vec<ATT>),
     /// Change the text of a Text node.
     ChangeText(NodeIdx, &'a Text),
+    /// Move the node currently at `node_idx` so that it sits right before the
+    /// node at `before_node_idx`. Both indices refer to the *old* (currently
+    /// rendered) tree, since the real DOM nodes they point at already exist.
+    /// Emitted when reconciling a keyed list reorders an existing child
+    /// instead of replacing it.
+    MoveNodeBefore(&'a T, NodeIdx, NodeIdx),
+    /// Insert new children right before the node at `before_node_idx`, which
+    /// is a child of the parent at `node_idx`. Like `AppendChildren`, but for
+    /// keyed children that land in the middle of the list instead of the end.
+    InsertBefore(&'a T, NodeIdx, NodeIdx, Vec<&'a Node<T, ATT, EVENT, MSG>>),
+    /// Remove the node at `node_idx` from its parent. Emitted for keyed
+    /// children that are present in the old list but absent from the new one.
+    RemoveNode(&'a T, NodeIdx),
+    /// Move the node currently at `node_idx` to the end of its parent's
+    /// children, e.g. via the real DOM's `appendChild` on an existing node
+    /// (which moves rather than clones it). Emitted instead of
+    /// `MoveNodeBefore` when a moved keyed child has no later sibling left
+    /// to anchor on, i.e. it belongs at the very end of the list.
+    MoveNodeToEnd(&'a T, NodeIdx),
 }
 
 type NodeIdx = usize;
@@ -83,6 +104,423 @@ where
             Patch::AddEventListener(_tag, node_idx, _) => *node_idx,
             Patch::RemoveEventListener(_tag, node_idx, _) => *node_idx,
             Patch::ChangeText(node_idx, _) => *node_idx,
+            Patch::MoveNodeBefore(_tag, node_idx, _before_idx) => *node_idx,
+            Patch::InsertBefore(_tag, node_idx, _before_idx, _) => *node_idx,
+            Patch::RemoveNode(_tag, node_idx) => *node_idx,
+            Patch::MoveNodeToEnd(_tag, node_idx) => *node_idx,
         }
     }
 }
+
+/// Reconcile the children of a keyed list, matching old and new children by
+/// key instead of by position so that reordering a list moves existing DOM
+/// nodes instead of destroying and rebuilding them.
+///
+/// `key_of` extracts the key of a child. This function requires that
+/// `old_children` and `new_children` are already filtered down to the keyed
+/// subset of a node's children; it does not diff unkeyed children at all, so
+/// callers must diff those positionally themselves before calling this (in
+/// debug builds, passing a child with no key trips a `debug_assert`). `old_base_idx`
+/// is the depth-first index of `old_children[0]`; the index of each later
+/// child is counted on from it by the sizes of the subtrees before it in
+/// `old_children`, so unkeyed siblings filtered out from between keyed
+/// children are not counted.
+///
+/// Children whose key appears in both lists are matched up; the matched old
+/// positions that already appear in increasing order (the longest increasing
+/// subsequence) are left alone, and every other matched child is moved with
+/// `MoveNodeBefore`, or with `MoveNodeToEnd` when nothing to its right has a
+/// known final position. Keys only in `new_children` are inserted, and keys
+/// only in `old_children` are removed.
+pub fn diff_keyed_children<'a, T, ATT, EVENT, MSG, K, KeyFn>(
+    tag: &'a T,
+    parent_idx: NodeIdx,
+    old_children: &'a [Node<T, ATT, EVENT, MSG>],
+    new_children: &'a [Node<T, ATT, EVENT, MSG>],
+    old_base_idx: NodeIdx,
+    key_of: KeyFn,
+) -> Vec<Patch<'a, T, ATT, EVENT, MSG>>
+where
+    ATT: Clone,
+    K: Eq + Hash,
+    KeyFn: Fn(&Node<T, ATT, EVENT, MSG>) -> Option<K>,
+{
+    debug_assert!(
+        old_children.iter().all(|child| key_of(child).is_some())
+            && new_children.iter().all(|child| key_of(child).is_some()),
+        "diff_keyed_children requires every child to have a key; filter out \
+         unkeyed children and diff them positionally before calling this"
+    );
+
+    // Depth-first index of each old child: each one follows the whole
+    // subtree of the child before it.
+    let mut old_node_idx: Vec<NodeIdx> = Vec::with_capacity(old_children.len());
+    let mut next_idx = old_base_idx;
+    for child in old_children {
+        old_node_idx.push(next_idx);
+        let mut stack = vec![child];
+        while let Some(node) = stack.pop() {
+            next_idx += 1;
+            if let Node::Element(element) = node {
+                stack.extend(element.children.iter());
+            }
+        }
+    }
+
+    let old_key_to_idx: HashMap<K, usize> = old_children
+        .iter()
+        .enumerate()
+        .filter_map(|(i, child)| key_of(child).map(|k| (k, i)))
+        .collect();
+
+    // For each new child, the index into `old_children` it matches, if any.
+    let matched: Vec<Option<usize>> = new_children
+        .iter()
+        .map(|child| key_of(child).and_then(|k| old_key_to_idx.get(&k).copied()))
+        .collect();
+
+    // The matched old positions, in new-child order. Running LIS over this
+    // tells us which matched children are already in the right relative
+    // order and therefore don't need to move.
+    let matched_old_positions: Vec<usize> = matched.iter().filter_map(|m| *m).collect();
+    let stays: HashSet<usize> = longest_increasing_subsequence(&matched_old_positions)
+        .into_iter()
+        .map(|seq_idx| matched_old_positions[seq_idx])
+        .collect();
+
+    let mut patches = Vec::new();
+
+    // Walk the new children right to left, tracking the old index of the
+    // nearest child to our right whose final position we already know.
+    // Every move or insert is placed right before that anchor; once we reach
+    // the rightmost unanchored child, there's nothing to its right yet, so
+    // it's appended/moved to the end instead.
+    //
+    // Unmatched children are buffered in `pending_inserts` rather than
+    // pushed as they're seen, because a run of several adjacent unmatched
+    // children must land in one `InsertBefore`/`AppendChildren` patch, in
+    // ascending order; emitting one patch per child against a stale anchor
+    // would insert each one before the last, reversing the run.
+    let mut anchor: Option<NodeIdx> = None;
+    let mut pending_inserts: Vec<&'a Node<T, ATT, EVENT, MSG>> = Vec::new();
+    for (new_idx, m) in matched.iter().enumerate().rev() {
+        match m {
+            Some(old_idx) if stays.contains(old_idx) => {
+                flush_pending_inserts(&mut pending_inserts, &mut patches, tag, parent_idx, anchor);
+                anchor = Some(old_node_idx[*old_idx]);
+            }
+            Some(old_idx) => {
+                let moved_idx = old_node_idx[*old_idx];
+                match anchor {
+                    Some(before) => {
+                        patches.push(Patch::MoveNodeBefore(tag, moved_idx, before))
+                    }
+                    // nothing to our right has a known final position yet,
+                    // so this child belongs at the very end of the list
+                    None => patches.push(Patch::MoveNodeToEnd(tag, moved_idx)),
+                }
+                // Flush against the same (pre-update) anchor the move just
+                // used: any pending inserts sit between this moved child and
+                // that anchor, so they must land after the move patch above,
+                // not before it, or the move would slip in between them and
+                // the anchor, leaving them in front of the moved child.
+                flush_pending_inserts(&mut pending_inserts, &mut patches, tag, parent_idx, anchor);
+                anchor = Some(moved_idx);
+            }
+            None => pending_inserts.push(&new_children[new_idx]),
+        }
+    }
+    flush_pending_inserts(&mut pending_inserts, &mut patches, tag, parent_idx, anchor);
+
+    let new_keys: HashSet<K> = new_children.iter().filter_map(&key_of).collect();
+    for (old_idx, child) in old_children.iter().enumerate() {
+        if let Some(key) = key_of(child) {
+            if !new_keys.contains(&key) {
+                patches.push(Patch::RemoveNode(tag, old_node_idx[old_idx]));
+            }
+        }
+    }
+
+    patches
+}
+
+/// Drain `pending_inserts` (collected in right-to-left, i.e. descending,
+/// order) into a single `InsertBefore`/`AppendChildren` patch with the
+/// children restored to ascending order, anchored right before `anchor` (or
+/// appended to the end if there's no anchor yet). No-op if nothing is
+/// pending.
+fn flush_pending_inserts<'a, T, ATT, EVENT, MSG>(
+    pending_inserts: &mut Vec<&'a Node<T, ATT, EVENT, MSG>>,
+    patches: &mut Vec<Patch<'a, T, ATT, EVENT, MSG>>,
+    tag: &'a T,
+    parent_idx: NodeIdx,
+    anchor: Option<NodeIdx>,
+) where
+    ATT: Clone,
+{
+    if pending_inserts.is_empty() {
+        return;
+    }
+    pending_inserts.reverse();
+    let nodes = std::mem::take(pending_inserts);
+    match anchor {
+        Some(before) => patches.push(Patch::InsertBefore(tag, parent_idx, before, nodes)),
+        None => patches.push(Patch::AppendChildren(tag, parent_idx, nodes)),
+    }
+}
+
+/// Return the indices (into `seq`) of one longest strictly increasing
+/// subsequence of `seq`, in ascending order. Runs in O(n log n) using
+/// patience sorting: `tails[k]` holds the index of the smallest tail value
+/// seen so far for an increasing subsequence of length `k + 1`.
+fn longest_increasing_subsequence(seq: &[usize]) -> Vec<usize> {
+    let mut tails: Vec<usize> = Vec::new();
+    let mut predecessors: Vec<Option<usize>> = vec![None; seq.len()];
+
+    for (i, &value) in seq.iter().enumerate() {
+        let pos = tails.partition_point(|&t| seq[t] < value);
+        if pos > 0 {
+            predecessors[i] = Some(tails[pos - 1]);
+        }
+        if pos == tails.len() {
+            tails.push(i);
+        } else {
+            tails[pos] = i;
+        }
+    }
+
+    let mut lis: Vec<usize> = Vec::with_capacity(tails.len());
+    let mut next = tails.last().copied();
+    while let Some(i) = next {
+        lis.push(i);
+        next = predecessors[i];
+    }
+    lis.reverse();
+    lis
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Element;
+
+    // Every child is an empty element whose tag doubles as its key, so
+    // `key_of` below can identify a child without needing a separate `key`
+    // attribute.
+    type TestNode = Node<&'static str, &'static str, (), ()>;
+
+    fn keyed(tag: &'static str) -> TestNode {
+        Node::Element(Element {
+            tag,
+            attrs: Vec::new(),
+            children: Vec::new(),
+        })
+    }
+
+    fn key_of(node: &TestNode) -> Option<&'static str> {
+        match node {
+            Node::Element(element) => Some(element.tag),
+            Node::Text(_) => None,
+        }
+    }
+
+    fn tags_of<'a>(nodes: &[&'a TestNode]) -> Vec<&'static str> {
+        nodes.iter().filter_map(|n| key_of(n)).collect()
+    }
+
+    const PARENT_TAG: &str = "ul";
+    const PARENT_IDX: NodeIdx = 0;
+    const OLD_BASE_IDX: NodeIdx = 1;
+
+    fn diff<'a>(
+        old: &'a [TestNode],
+        new: &'a [TestNode],
+    ) -> Vec<Patch<'a, &'static str, &'static str, (), ()>> {
+        diff_keyed_children(&PARENT_TAG, PARENT_IDX, old, new, OLD_BASE_IDX, key_of)
+    }
+
+    #[test]
+    fn all_new_children_are_appended_in_order() {
+        let old = [keyed("a"), keyed("b"), keyed("c")];
+        let new = [keyed("d"), keyed("e"), keyed("f")];
+        let patches = diff(&old, &new);
+
+        match &patches[0] {
+            Patch::AppendChildren(_, PARENT_IDX, nodes) => {
+                assert_eq!(tags_of(nodes), vec!["d", "e", "f"]);
+            }
+            other => panic!("expected AppendChildren first, got {:?}", other),
+        }
+        let removed: HashSet<NodeIdx> = patches[1..]
+            .iter()
+            .map(|p| match p {
+                Patch::RemoveNode(_, idx) => *idx,
+                other => panic!("expected RemoveNode, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(removed, [OLD_BASE_IDX, OLD_BASE_IDX + 1, OLD_BASE_IDX + 2].into());
+    }
+
+    #[test]
+    fn leading_multi_item_insert_keeps_ascending_order() {
+        let old = [keyed("a"), keyed("b"), keyed("c"), keyed("d")];
+        let new = [
+            keyed("x"),
+            keyed("y"),
+            keyed("a"),
+            keyed("b"),
+            keyed("c"),
+            keyed("d"),
+        ];
+        let patches = diff(&old, &new);
+
+        assert_eq!(patches.len(), 1);
+        match &patches[0] {
+            Patch::InsertBefore(_, PARENT_IDX, before, nodes) => {
+                assert_eq!(*before, OLD_BASE_IDX);
+                assert_eq!(tags_of(nodes), vec!["x", "y"]);
+            }
+            other => panic!("expected InsertBefore, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn middle_multi_item_insert_keeps_ascending_order() {
+        let old = [keyed("a"), keyed("d")];
+        let new = [keyed("a"), keyed("b"), keyed("c"), keyed("d")];
+        let patches = diff(&old, &new);
+
+        assert_eq!(patches.len(), 1);
+        match &patches[0] {
+            Patch::InsertBefore(_, PARENT_IDX, before, nodes) => {
+                assert_eq!(*before, OLD_BASE_IDX + 1);
+                assert_eq!(tags_of(nodes), vec!["b", "c"]);
+            }
+            other => panic!("expected InsertBefore, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn trailing_multi_item_insert_with_no_anchor_appends_in_order() {
+        let old = [keyed("a")];
+        let new = [keyed("a"), keyed("b"), keyed("c")];
+        let patches = diff(&old, &new);
+
+        assert_eq!(patches.len(), 1);
+        match &patches[0] {
+            Patch::AppendChildren(_, PARENT_IDX, nodes) => {
+                assert_eq!(tags_of(nodes), vec!["b", "c"]);
+            }
+            other => panic!("expected AppendChildren, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn reordered_children_move_the_minimal_set() {
+        // `b` and `c` are already in increasing relative order, so only `a`
+        // needs to move; it has nothing to its right with a known position
+        // yet, so it moves to the end instead of before an anchor.
+        let old = [keyed("a"), keyed("b"), keyed("c")];
+        let new = [keyed("b"), keyed("c"), keyed("a")];
+        let patches = diff(&old, &new);
+
+        assert_eq!(patches, vec![Patch::MoveNodeToEnd(&PARENT_TAG, OLD_BASE_IDX)]);
+    }
+
+    #[test]
+    fn missing_key_is_removed() {
+        let old = [keyed("a"), keyed("b"), keyed("c")];
+        let new = [keyed("a"), keyed("c")];
+        let patches = diff(&old, &new);
+
+        assert_eq!(
+            patches,
+            vec![Patch::RemoveNode(&PARENT_TAG, OLD_BASE_IDX + 1)]
+        );
+    }
+
+    // Replay `patches` against `old`'s tags to get the resulting DOM order,
+    // so a move/insert combination can be checked by its actual effect
+    // rather than by which patch variants happen to be emitted. `idx` values
+    // always address nodes by their position in `old` (possibly already
+    // relocated by an earlier patch in the list), matching how the real
+    // patching engine resolves `NodeIdx`.
+    fn apply_patches<'a>(
+        old: &[TestNode],
+        patches: &[Patch<'a, &'static str, &'static str, (), ()>],
+    ) -> Vec<&'static str> {
+        let idx_to_tag: HashMap<NodeIdx, &'static str> = old
+            .iter()
+            .enumerate()
+            .map(|(i, child)| (OLD_BASE_IDX + i, key_of(child).unwrap()))
+            .collect();
+        let mut dom: Vec<&'static str> = old.iter().filter_map(key_of).collect();
+        let pos_of = |dom: &[&'static str], tag: &'static str| {
+            dom.iter().position(|&t| t == tag).unwrap()
+        };
+
+        for patch in patches {
+            match patch {
+                Patch::MoveNodeBefore(_, idx, before) => {
+                    let tag = idx_to_tag[idx];
+                    let from = pos_of(&dom, tag);
+                    dom.remove(from);
+                    let before_pos = pos_of(&dom, idx_to_tag[before]);
+                    dom.insert(before_pos, tag);
+                }
+                Patch::MoveNodeToEnd(_, idx) => {
+                    let tag = idx_to_tag[idx];
+                    let from = pos_of(&dom, tag);
+                    dom.remove(from);
+                    dom.push(tag);
+                }
+                Patch::InsertBefore(_, _, before, nodes) => {
+                    let before_pos = pos_of(&dom, idx_to_tag[before]);
+                    for (offset, tag) in tags_of(nodes).into_iter().enumerate() {
+                        dom.insert(before_pos + offset, tag);
+                    }
+                }
+                Patch::AppendChildren(_, _, nodes) => dom.extend(tags_of(nodes)),
+                Patch::RemoveNode(_, idx) => {
+                    let tag = idx_to_tag[idx];
+                    let from = pos_of(&dom, tag);
+                    dom.remove(from);
+                }
+                other => panic!("apply_patches: unhandled patch {:?}", other),
+            }
+        }
+        dom
+    }
+
+    #[test]
+    fn move_adjacent_to_insert_preserves_new_order() {
+        // c and e move (not part of the LIS {a, b}), f is a brand new node
+        // inserted right after the moved c (before a), and d is dropped.
+        let old = [keyed("a"), keyed("b"), keyed("c"), keyed("d"), keyed("e")];
+        let new = [keyed("c"), keyed("f"), keyed("a"), keyed("e"), keyed("b")];
+        let patches = diff(&old, &new);
+
+        assert_eq!(
+            apply_patches(&old, &patches),
+            vec!["c", "f", "a", "e", "b"]
+        );
+    }
+
+    #[test]
+    fn move_to_end_adjacent_to_insert_preserves_new_order() {
+        // b and c stay in relative order, so a (the odd one out) moves to
+        // the end; x is a brand new node inserted right after it, with
+        // nothing else to a's right, so this exercises `MoveNodeToEnd`
+        // followed by an anchor-less `AppendChildren` rather than
+        // `move_adjacent_to_insert_preserves_new_order`'s `MoveNodeBefore`
+        // case.
+        let old = [keyed("a"), keyed("b"), keyed("c")];
+        let new = [keyed("b"), keyed("c"), keyed("a"), keyed("x")];
+        let patches = diff(&old, &new);
+
+        assert_eq!(
+            apply_patches(&old, &patches),
+            vec!["b", "c", "a", "x"]
+        );
+    }
+}
@@ -5,7 +5,9 @@ use mapper::*;
 pub use sauron_vdom::{
     builder::{on, on_with_extractor},
     event::{
-        Coordinate, InputEvent, KeyEvent, Modifier, MouseButton, MouseEvent,
+        Coordinate, DataTransfer, DeltaMode, DragEvent, InputEvent, KeyEvent,
+        Modifier, MouseButton, MouseButtonSet, MouseEvent, StaticEvent, Touch,
+        TouchEvent, WheelEvent,
     },
     Callback,
 };
@@ -15,104 +17,122 @@ use wasm_bindgen::JsCast;
 ///
 /// This module convert browser events into sauron_vdom generic event
 pub mod mapper {
-    use log::*;
-
     use sauron_vdom::event::{
-        Coordinate, InputEvent, KeyEvent, Modifier, MouseButton, MouseEvent,
+        Coordinate, DataTransfer, DeltaMode, DragEvent, InputEvent, KeyEvent,
+        Modifier, MouseEvent, StaticEvent, Touch, TouchEvent, WheelEvent,
     };
     use wasm_bindgen::JsCast;
-    use web_sys::{self, EventTarget, HtmlInputElement, HtmlTextAreaElement};
 
     pub fn mouse_event_mapper(event: crate::Event) -> MouseEvent {
-        let mouse: &web_sys::MouseEvent =
-            event.0.dyn_ref().expect("Unable to cast to mouse event");
+        MouseEvent::unchecked_from_event(event.0)
+    }
+
+    pub fn keyboard_event_mapper(event: crate::Event) -> KeyEvent {
+        KeyEvent::unchecked_from_event(event.0)
+    }
+
+    pub fn input_event_mapper(event: crate::Event) -> InputEvent {
+        InputEvent::unchecked_from_event(event.0)
+    }
+
+    fn touch_list_to_vec(touches: web_sys::TouchList) -> Vec<Touch> {
+        (0..touches.length())
+            .filter_map(|i| touches.item(i))
+            .map(|touch| Touch {
+                identifier: touch.identifier(),
+                client_x: touch.client_x(),
+                client_y: touch.client_y(),
+                screen_x: touch.screen_x(),
+                screen_y: touch.screen_y(),
+                page_x: touch.page_x(),
+                page_y: touch.page_y(),
+                radius_x: touch.radius_x(),
+                radius_y: touch.radius_y(),
+                force: touch.force() as f64,
+            })
+            .collect()
+    }
+
+    pub fn wheel_event_mapper(event: crate::Event) -> WheelEvent {
+        let wheel: &web_sys::WheelEvent =
+            event.0.dyn_ref().expect("Unable to cast to wheel event");
 
         let coordinate = Coordinate {
-            client_x: mouse.client_x(),
-            client_y: mouse.client_y(),
-            movement_x: mouse.movement_x(),
-            movement_y: mouse.movement_y(),
-            offset_x: mouse.offset_x(),
-            offset_y: mouse.offset_y(),
-            screen_x: mouse.screen_x(),
-            screen_y: mouse.screen_y(),
-            x: mouse.x(),
-            y: mouse.y(),
+            client_x: wheel.client_x(),
+            client_y: wheel.client_y(),
+            movement_x: wheel.movement_x(),
+            movement_y: wheel.movement_y(),
+            offset_x: wheel.offset_x(),
+            offset_y: wheel.offset_y(),
+            screen_x: wheel.screen_x(),
+            screen_y: wheel.screen_y(),
+            x: wheel.x(),
+            y: wheel.y(),
         };
         let modifier = Modifier {
-            alt_key: mouse.alt_key(),
-            ctrl_key: mouse.ctrl_key(),
-            meta_key: mouse.meta_key(),
-            shift_key: mouse.shift_key(),
-        };
-        let buttons = match mouse.button() {
-            0 => MouseButton::Left,
-            1 => MouseButton::Middle,
-            2 => MouseButton::Left,
-            3 => MouseButton::WheelUp,
-            4 => MouseButton::WheelDown,
-            _ => Default::default(), // defaults to left
+            alt_key: wheel.alt_key(),
+            ctrl_key: wheel.ctrl_key(),
+            meta_key: wheel.meta_key(),
+            shift_key: wheel.shift_key(),
         };
-        let r#type = match &*event.0.type_() {
-            "click" => "click",
-            "mouseup" => "mouseup",
-            "mousedown" => "mousedown",
-            "mousemove" => "mousemove",
-            "dblclick" => "dblclick",
-            _e => {
-                error!("unhandled event type: {}", _e);
-                panic!("unhandled event type: {}", _e);
-            }
-        };
-        MouseEvent {
-            r#type,
+
+        WheelEvent {
             coordinate,
             modifier,
-            buttons,
+            delta_x: wheel.delta_x(),
+            delta_y: wheel.delta_y(),
+            delta_z: wheel.delta_z(),
+            delta_mode: DeltaMode::from(wheel.delta_mode()),
         }
     }
 
-    pub fn keyboard_event_mapper(event: crate::Event) -> KeyEvent {
-        if let Some(key_event) = event.0.dyn_ref::<web_sys::KeyboardEvent>() {
-            let modifier = Modifier {
-                alt_key: key_event.alt_key(),
-                ctrl_key: key_event.ctrl_key(),
-                meta_key: key_event.meta_key(),
-                shift_key: key_event.shift_key(),
-            };
-            KeyEvent {
-                key: key_event.key(),
-                modifier,
-                repeat: key_event.repeat(),
-                location: key_event.location(),
-            }
-        } else {
-            //FIXME: not a keyboard event just make something up,
-            //maybe make the return type optional?
-            KeyEvent::default()
+    pub fn drag_event_mapper(event: crate::Event) -> DragEvent {
+        let drag: &web_sys::DragEvent =
+            event.0.dyn_ref().expect("Unable to cast to drag event");
+
+        let coordinate = Coordinate {
+            client_x: drag.client_x(),
+            client_y: drag.client_y(),
+            movement_x: drag.movement_x(),
+            movement_y: drag.movement_y(),
+            offset_x: drag.offset_x(),
+            offset_y: drag.offset_y(),
+            screen_x: drag.screen_x(),
+            screen_y: drag.screen_y(),
+            x: drag.x(),
+            y: drag.y(),
+        };
+        let modifier = Modifier {
+            alt_key: drag.alt_key(),
+            ctrl_key: drag.ctrl_key(),
+            meta_key: drag.meta_key(),
+            shift_key: drag.shift_key(),
+        };
+
+        DragEvent {
+            coordinate,
+            modifier,
+            data_transfer: drag.data_transfer().map(DataTransfer::new),
         }
     }
 
-    pub fn input_event_mapper(event: crate::Event) -> InputEvent {
-        let target: EventTarget =
-            event.0.target().expect("Unable to get event target");
-        let input_event = if let Some(input) =
-            target.dyn_ref::<HtmlInputElement>()
-        {
-            Some(InputEvent {
-                value: input.value(),
-            })
-        } else if let Some(textarea) = target.dyn_ref::<HtmlTextAreaElement>() {
-            Some(InputEvent {
-                value: textarea.value(),
-            })
-        } else {
-            None
+    pub fn touch_event_mapper(event: crate::Event) -> TouchEvent {
+        let touch_event: &web_sys::TouchEvent =
+            event.0.dyn_ref().expect("Unable to cast to touch event");
+
+        let modifier = Modifier {
+            alt_key: touch_event.alt_key(),
+            ctrl_key: touch_event.ctrl_key(),
+            meta_key: touch_event.meta_key(),
+            shift_key: touch_event.shift_key(),
         };
 
-        input_event.expect(
-            "Expecting an input event from input element or textarea element",
-        )
+        TouchEvent {
+            touches: touch_list_to_vec(touch_event.touches()),
+            target_touches: touch_list_to_vec(touch_event.target_touches()),
+            changed_touches: touch_list_to_vec(touch_event.changed_touches()),
+            modifier,
+        }
     }
 }
 
@@ -152,6 +172,24 @@ macro_rules! declare_events {
     }
 }
 
+/// Register a callback for any event type that implements `StaticEvent`.
+/// This is how third-party crates add their own strongly-typed events — a
+/// `CustomEvent` with a typed `detail`, media events, etc. — without
+/// needing to patch `declare_events!`: implement `StaticEvent` for the event
+/// type and call `on_event::<TheEvent, _>(cb)`.
+pub fn on_event<E, CB, MSG>(cb: CB) -> crate::Attribute<MSG>
+where
+    E: StaticEvent,
+    CB: Fn(E) -> MSG + 'static,
+    MSG: 'static,
+{
+    on_with_extractor(
+        E::EVENT_TYPE,
+        |event: crate::Event| E::unchecked_from_event(event.0),
+        cb,
+    )
+}
+
 #[inline]
 pub fn onscroll<CB, MSG>(cb: CB) -> crate::Attribute<MSG>
 where
@@ -256,10 +294,97 @@ declare_events! {
     onpointerlockchange : pointerlockchange =>MouseEvent => mouse_event_mapper;
     onpointerlockerror : pointerlockerror =>MouseEvent => mouse_event_mapper;
     onselect : select => MouseEvent => mouse_event_mapper;
-    onwheel : wheel => MouseEvent => mouse_event_mapper;
     ondoubleclick : dblclick => MouseEvent => mouse_event_mapper;
 }
 
+// wheel events
+declare_events! {
+    onwheel : wheel => WheelEvent => wheel_event_mapper;
+}
+
+/// a version of a drag event where you can choose to prevent_default and/or
+/// stop_propagation, mirroring `onclick_with`.
+fn ondrag_event_with<CB, MSG>(
+    event_name: &'static str,
+    prevent_default: bool,
+    stop_propagation: bool,
+    cb: CB,
+) -> crate::Attribute<MSG>
+where
+    CB: Fn(DragEvent) -> MSG + 'static,
+    MSG: 'static,
+{
+    on_with_extractor(
+        event_name,
+        move |event: crate::Event| {
+            if prevent_default {
+                event.prevent_default();
+            }
+            if stop_propagation {
+                event.stop_propagation();
+            }
+            drag_event_mapper(event)
+        },
+        cb,
+    )
+}
+
+/// a version of `ondrop` where you can choose to prevent_default and/or
+/// stop_propagation
+pub fn ondrop_with<CB, MSG>(
+    prevent_default: bool,
+    stop_propagation: bool,
+    cb: CB,
+) -> crate::Attribute<MSG>
+where
+    CB: Fn(DragEvent) -> MSG + 'static,
+    MSG: 'static,
+{
+    ondrag_event_with("drop", prevent_default, stop_propagation, cb)
+}
+
+/// a version of `ondragover` where you can choose to prevent_default and/or
+/// stop_propagation. The DnD spec requires `preventDefault` on `dragover`
+/// for the element to become a valid drop target at all — without it the
+/// browser will never fire `drop`.
+pub fn ondragover_with<CB, MSG>(
+    prevent_default: bool,
+    stop_propagation: bool,
+    cb: CB,
+) -> crate::Attribute<MSG>
+where
+    CB: Fn(DragEvent) -> MSG + 'static,
+    MSG: 'static,
+{
+    ondrag_event_with("dragover", prevent_default, stop_propagation, cb)
+}
+
+/// a version of `ondragenter` where you can choose to prevent_default and/or
+/// stop_propagation. Some browsers also require `preventDefault` on
+/// `dragenter` for the subsequent `dragover`/`drop` to be honored.
+pub fn ondragenter_with<CB, MSG>(
+    prevent_default: bool,
+    stop_propagation: bool,
+    cb: CB,
+) -> crate::Attribute<MSG>
+where
+    CB: Fn(DragEvent) -> MSG + 'static,
+    MSG: 'static,
+{
+    ondrag_event_with("dragenter", prevent_default, stop_propagation, cb)
+}
+
+// drag and drop events
+declare_events! {
+    ondragstart : dragstart => DragEvent => drag_event_mapper;
+    ondrag : drag => DragEvent => drag_event_mapper;
+    ondragenter : dragenter => DragEvent => drag_event_mapper;
+    ondragover : dragover => DragEvent => drag_event_mapper;
+    ondragleave : dragleave => DragEvent => drag_event_mapper;
+    ondrop : drop => DragEvent => drag_event_mapper;
+    ondragend : dragend => DragEvent => drag_event_mapper;
+}
+
 // keyboard events
 declare_events! {
     onkeydown : keydown => KeyEvent => keyboard_event_mapper;
@@ -283,6 +408,14 @@ declare_events! {
     oninput : input => InputEvent => input_event_mapper;
     onchange : change => InputEvent => input_event_mapper;
 }
+// touch events
+declare_events! {
+    ontouchstart : touchstart => TouchEvent => touch_event_mapper;
+    ontouchmove : touchmove => TouchEvent => touch_event_mapper;
+    ontouchend : touchend => TouchEvent => touch_event_mapper;
+    ontouchcancel : touchcancel => TouchEvent => touch_event_mapper;
+}
+
 declare_events! {
     onbroadcast : broadcast;
     //CheckboxStateChange